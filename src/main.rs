@@ -23,6 +23,13 @@ use syntect::parsing::SyntaxSet;
 use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
 use termimad::{Area, MadSkin};
 
+mod pdf;
+mod sanitize;
+mod terminal_image;
+
+/// Theme used for `Code` slides when the deck doesn't request one.
+const DEFAULT_THEME: &str = "Solarized (light)";
+
 #[derive(Debug, Deserialize)]
 enum FileTypes {
     Markdown(String),
@@ -32,6 +39,7 @@ enum FileTypes {
     Print(String),
     FIGlet(String),
     Code(String),
+    Pdf(String),
 }
 
 fn text_size(s: &str) -> (usize,usize) {
@@ -65,51 +73,72 @@ impl FileTypes {
         Ok(())
     }
 
-    fn write_text(w: &mut impl Write, txt:&String) -> Result<()> {
+    pub(crate) fn write_text(w: &mut impl Write, txt: &String, scroll: Scroll) -> Result<()> {
+        let txt = sanitize::sanitize(txt);
         let (width, height) = terminal::size().unwrap();
-        let top = height.saturating_sub(txt.lines().count() as u16)  /2;
-        
-        for (idx,l) in txt.lines().enumerate() {
-            let x = width.saturating_sub(l.len() as u16)  /2;
-            w.queue(cursor::MoveTo(x,top + idx as u16))?;
+        let lines: Vec<&str> = txt.lines().collect();
+        let top = height.saturating_sub(lines.len() as u16) / 2;
+
+        for (idx, l) in lines.iter().enumerate().skip(scroll.v as usize) {
+            let row = top + (idx - scroll.v as usize) as u16;
+            if row >= height {
+                break;
+            }
+
+            let x = width.saturating_sub(l.len() as u16) / 2;
+            let x = x.saturating_sub(scroll.h);
+            w.queue(cursor::MoveTo(x, row))?;
             w.write_all(l.as_bytes())?;
         }
-        
+
         w.flush()?;
 
         Ok(())
     }
 
-    fn show(&self, w: &mut impl Write, margin: usize) -> Result<()> {
+    fn show(
+        &self,
+        w: &mut impl Write,
+        margin: usize,
+        theme: Option<&str>,
+        scroll: Scroll,
+        zoom: bool,
+        pdf_page: usize,
+    ) -> Result<()> {
         match self {
+            FileTypes::Image(path) => {
+                let (cols, rows) = terminal_image::terminal_cells()?;
+                terminal_image::render(w, Path::new(path), cols, rows)?;
+            }
             FileTypes::GifAnimation(path) => {
+                // None of our inline-graphics protocols animate, so gifs
+                // still go through `viu`: `-s` shows the first frame here,
+                // and the slide's action (Enter) plays the animation.
                 disable_raw_mode()?;
                 Command::new("viu").arg("-s").arg(path).status()?;
                 enable_raw_mode()?;
             }
-            FileTypes::Image(path) => {
-                disable_raw_mode()?;
-                let (w, h) = terminal::size()?;
-                Command::new("viu")
-                    .arg(format!("-w{}", w))
-                    .arg(format!("-h{}", h))
-                    .arg(path)
-                    .status()?;
-                enable_raw_mode()?;
+            FileTypes::Pdf(path) => {
+                let (cols, rows) = terminal_image::terminal_cells()?;
+                pdf::show(w, path, pdf_page, cols, rows, scroll)?;
             }
             FileTypes::Print(txt) => {
-                Self::write_text(w,txt)?;
+                Self::write_text(w, txt, scroll)?;
             }
             FileTypes::Markdown(path) => {
                 let (width, height) = terminal::size().unwrap();
-                let markdown = fs::read_to_string(Path::new(path))?;
+                let raw = fs::read(Path::new(path))?;
+                if sanitize::is_binary(&raw) {
+                    return Self::write_text(w, &format!("Cannot display binary file:\n{}", path), scroll);
+                }
+                let markdown = sanitize::sanitize(&String::from_utf8_lossy(&raw));
                 let (text_w,_) = text_size(markdown.as_str());
 
                 let area_w = text_w.min(width as usize- (margin*2)) as u16;
                 let area_h = height - (margin as u16 * 2);
 
-                let x = 0.max((width - area_w) / 2);
-                let y = 0.max((height - area_h) / 2);
+                let x = 0.max((width - area_w) / 2).saturating_sub(scroll.h);
+                let y = 0.max((height - area_h) / 2).saturating_sub(scroll.v);
 
                 MadSkin::default()
                     .write_in_area(&markdown, &Area::new(x, y, area_w, area_h))
@@ -117,27 +146,105 @@ impl FileTypes {
             }
             FileTypes::Open(path) => {
                 let txt = format!("External file:\n{}\n\npress enter to open",path);
-                Self::write_text(w, &txt)?;
+                Self::write_text(w, &txt, scroll)?;
             }
             FileTypes::Code(path) => {
                 let (width, height) = terminal::size().unwrap();
-                let content = fs::read_to_string(Path::new(path))?;
-                let text_size = text_size(content.as_str()); 
-                let x = (width - text_size.0 as u16)/2;
-                let y = (height - text_size.1 as u16)/2;
+                let raw = fs::read(Path::new(path))?;
+                if sanitize::is_binary(&raw) {
+                    return Self::write_text(w, &format!("Cannot display binary file:\n{}", path), scroll);
+                }
+                let content = sanitize::sanitize(&String::from_utf8_lossy(&raw));
+                let text_size = text_size(content.as_str());
+
+                // Zoom fills the screen by blowing each character up into a
+                // `scale`x`scale` block of cells, rather than just
+                // top-left-aligning the unscaled text.
+                let scale = if zoom {
+                    let scale_w = width / text_size.0.max(1) as u16;
+                    let scale_h = height / text_size.1.max(1) as u16;
+                    scale_w.min(scale_h).max(1).min(4)
+                } else {
+                    1
+                };
+
+                let (x, y, viewport_h) = if zoom {
+                    (0, 0, height)
+                } else {
+                    (
+                        width.saturating_sub(text_size.0 as u16) / 2,
+                        height.saturating_sub(text_size.1 as u16) / 2,
+                        height,
+                    )
+                };
+                let x = x.saturating_sub(scroll.h);
 
                 // Load these once at the start of your program
                 let ps = SyntaxSet::load_defaults_newlines();
                 let ts = ThemeSet::load_defaults();
 
-                let syntax = ps.find_syntax_by_extension("rs").unwrap();
-                let mut highlighter = HighlightLines::new(syntax, &ts.themes["Solarized (light)"]);
-
-                for (idx,line) in LinesWithEndings::from(content.as_str()).enumerate() {
+                let syntax = Path::new(path)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(|ext| ps.find_syntax_by_extension(ext))
+                    .or_else(|| ps.find_syntax_by_first_line(content.as_str()))
+                    .unwrap_or_else(|| ps.find_syntax_plain_text());
+
+                let theme = match theme {
+                    Some(name) => match ts.themes.get(name) {
+                        Some(t) => t,
+                        None => {
+                            // Don't silently fall back on a typo'd theme
+                            // name; the deck author needs to see it.
+                            return Self::write_text(
+                                w,
+                                &format!(
+                                    "Unknown theme \"{}\"\n\nsee ThemeSet::load_defaults() for valid names",
+                                    name
+                                ),
+                                scroll,
+                            );
+                        }
+                    },
+                    None => &ts.themes[DEFAULT_THEME],
+                };
+                let mut highlighter = HighlightLines::new(syntax, theme);
+
+                for (line_no, line) in LinesWithEndings::from(content.as_str()).enumerate() {
+                    // Keep feeding the highlighter every line (even skipped
+                    // ones) so its parse state stays correct when scrolled.
                     let ranges: Vec<(Style, &str)> = highlighter.highlight(line, &ps);
-                    let escaped = as_24_bit_terminal_escaped(&ranges[..], false);
-                    w.queue(cursor::MoveTo(x,y+idx as u16))?;
-                    w.write_all(escaped.to_string().as_bytes())?;
+
+                    if line_no < scroll.v as usize {
+                        continue;
+                    }
+                    let row = y + ((line_no - scroll.v as usize) as u16) * scale;
+                    if row >= viewport_h {
+                        break;
+                    }
+
+                    let scaled: Vec<(Style, String)> = ranges
+                        .iter()
+                        .map(|(style, text)| {
+                            let repeated: String = text
+                                .chars()
+                                .flat_map(|c| std::iter::repeat(c).take(scale as usize))
+                                .collect();
+                            (*style, repeated)
+                        })
+                        .collect();
+                    let scaled_ranges: Vec<(Style, &str)> =
+                        scaled.iter().map(|(s, t)| (*s, t.as_str())).collect();
+                    let escaped = as_24_bit_terminal_escaped(&scaled_ranges[..], false);
+
+                    for dy in 0..scale {
+                        let target_row = row + dy;
+                        if target_row >= viewport_h {
+                            break;
+                        }
+                        w.queue(cursor::MoveTo(x, target_row))?;
+                        w.write_all(escaped.to_string().as_bytes())?;
+                    }
                 }
 
                 w.queue(cursor::MoveTo(0,0))?;
@@ -157,16 +264,35 @@ impl FileTypes {
     }
 }
 
+/// Per-slide scroll offset, in terminal cells. Reset whenever the current
+/// slide changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Scroll {
+    v: u16,
+    h: u16,
+}
+
 fn present(w: &mut impl Write, slides: &Slides) -> Result<()> {
     let mut idx = 0_usize;
     let mut margin = 2_usize;
+    let mut scroll = Scroll::default();
+    let mut zoom = false;
+    let mut pdf_page = 0_usize;
 
     loop {
         w.queue(Clear(ClearType::All))?.queue(MoveTo(0, 0))?;
         w.flush()?;
 
+        let pdf_page_count = match slides.files.get(idx) {
+            Some(FileTypes::Pdf(path)) => pdf::page_count(path),
+            _ => 1,
+        };
+
         if let Some(file) = slides.files.get(idx) {
-            file.show(w, margin)?;
+            // Zoom temporarily drops the margin to 0, like hunter's preview
+            // zoom, so zoomed content gets the whole screen to scale into.
+            let effective_margin = if zoom { 0 } else { margin };
+            file.show(w, effective_margin, slides.theme.as_deref(), scroll, zoom, pdf_page)?;
         } else {
             break;
         }
@@ -177,8 +303,23 @@ fn present(w: &mut impl Write, slides: &Slides) -> Result<()> {
             Input::Quit => {
                 break;
             }
-            Input::Previous => idx = idx.saturating_sub(1),
-            Input::Next => idx = idx.saturating_add(1),
+            Input::Previous => {
+                if pdf_page > 0 {
+                    pdf_page -= 1;
+                } else {
+                    idx = idx.saturating_sub(1);
+                    scroll = Scroll::default();
+                }
+            }
+            Input::Next => {
+                if pdf_page + 1 < pdf_page_count {
+                    pdf_page += 1;
+                } else {
+                    idx = idx.saturating_add(1);
+                    scroll = Scroll::default();
+                    pdf_page = 0;
+                }
+            }
             Input::Margin(plus) => {
                 if plus {
                     margin = margin.saturating_add(1)
@@ -186,6 +327,11 @@ fn present(w: &mut impl Write, slides: &Slides) -> Result<()> {
                     margin = margin.saturating_sub(1)
                 }
             }
+            Input::Scroll(ScrollDir::Up) => scroll.v = scroll.v.saturating_sub(1),
+            Input::Scroll(ScrollDir::Down) => scroll.v = scroll.v.saturating_add(1),
+            Input::Scroll(ScrollDir::Left) => scroll.h = scroll.h.saturating_sub(1),
+            Input::Scroll(ScrollDir::Right) => scroll.h = scroll.h.saturating_add(1),
+            Input::Zoom => zoom = !zoom,
             Input::Action => {
                 if let Some(file) = slides.files.get(idx) {
                     file.action(w)?;
@@ -203,10 +349,19 @@ enum Input {
     Previous,
     Next,
     Margin(bool),
+    Scroll(ScrollDir),
+    Zoom,
     Action,
     Quit,
 }
 
+enum ScrollDir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
 fn read_input() -> Result<Input> {
     let ev = event::read()?;
 
@@ -227,6 +382,34 @@ fn read_input() -> Result<Input> {
                 code: KeyCode::Char('-'),
                 ..
             } => Ok(Input::Margin(false)),
+            KeyEvent {
+                code: KeyCode::PageDown,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('j'),
+                ..
+            } => Ok(Input::Scroll(ScrollDir::Down)),
+            KeyEvent {
+                code: KeyCode::PageUp,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('k'),
+                ..
+            } => Ok(Input::Scroll(ScrollDir::Up)),
+            KeyEvent {
+                code: KeyCode::Char('h'),
+                ..
+            } => Ok(Input::Scroll(ScrollDir::Left)),
+            KeyEvent {
+                code: KeyCode::Char('l'),
+                ..
+            } => Ok(Input::Scroll(ScrollDir::Right)),
+            KeyEvent {
+                code: KeyCode::Char('z'),
+                ..
+            } => Ok(Input::Zoom),
             KeyEvent {
                 code: KeyCode::Esc, ..
             } => Ok(Input::Quit),
@@ -244,6 +427,10 @@ fn read_input() -> Result<Input> {
 #[derive(Debug, Deserialize)]
 struct Slides {
     files: Vec<FileTypes>,
+    /// Name of a `syntect` theme (from `ThemeSet::load_defaults()`) to use
+    /// for `Code` slides. Defaults to [`DEFAULT_THEME`] when unset.
+    #[serde(default)]
+    theme: Option<String>,
 }
 
 fn main() -> Result<()> {
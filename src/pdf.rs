@@ -0,0 +1,86 @@
+//! Rendering support for `Pdf` slides: rasterize pages with poppler's
+//! `pdftoppm`/`pdfinfo` CLI tools, falling back to plain-text extraction
+//! when no page-image backend is available.
+
+use crate::{terminal_image, FileTypes, Scroll};
+use crossterm::Result;
+use std::{
+    env, fs,
+    io::{self, Write},
+    process::Command,
+};
+
+fn io_err(e: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// Number of pages in `path`, as reported by `pdfinfo`. Defaults to `1` if
+/// `pdfinfo` isn't installed or its output can't be parsed, so a single-page
+/// deck still shows something rather than failing outright.
+pub fn page_count(path: &str) -> usize {
+    Command::new("pdfinfo")
+        .arg(path)
+        .output()
+        .ok()
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .and_then(|stdout| {
+            stdout
+                .lines()
+                .find_map(|line| line.strip_prefix("Pages:"))
+                .and_then(|n| n.trim().parse::<usize>().ok())
+        })
+        .unwrap_or(1)
+}
+
+/// Rasterize `page` (0-indexed) of `path` to a PNG via `pdftoppm` and decode
+/// it back in.
+fn rasterize(path: &str, page: usize) -> Result<image::DynamicImage> {
+    let prefix = env::temp_dir().join(format!("presentui-pdf-{}", std::process::id()));
+
+    Command::new("pdftoppm")
+        .arg("-png")
+        .arg("-f")
+        .arg((page + 1).to_string())
+        .arg("-l")
+        .arg((page + 1).to_string())
+        .arg("-singlefile")
+        .arg(path)
+        .arg(&prefix)
+        .status()
+        .map_err(io_err)?;
+
+    let png_path = prefix.with_extension("png");
+    let img = image::open(&png_path).map_err(io_err)?;
+    let _ = fs::remove_file(&png_path);
+
+    Ok(img)
+}
+
+/// Extract the raw text of `path`, used when rasterizing isn't possible
+/// (e.g. `pdftoppm` isn't installed).
+fn extract_text(path: &str) -> Result<String> {
+    pdf_extract::extract_text(path).map_err(io_err)
+}
+
+/// Show page `page` (0-indexed) of the PDF at `path`, either as an inline
+/// image or, if rasterizing fails, as the plain text extracted from the
+/// whole document. The text fallback goes through
+/// [`FileTypes::write_text`], which centers it and escapes the same control
+/// bytes every other text slide is guarded against — extracted PDF text is
+/// just as untrusted.
+pub fn show(
+    w: &mut impl Write,
+    path: &str,
+    page: usize,
+    cols: u16,
+    rows: u16,
+    scroll: Scroll,
+) -> Result<()> {
+    match rasterize(path, page) {
+        Ok(img) => terminal_image::render_image(w, &img, cols, rows),
+        Err(_) => {
+            let text = extract_text(path)?;
+            FileTypes::write_text(w, &text, scroll)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Guards against hostile or malformed slide content reaching the terminal
+//! verbatim. A file containing raw escape sequences shouldn't be able to
+//! move the cursor, recolor the screen, or otherwise corrupt the display
+//! mid-presentation.
+
+/// Bytes sniffed from the start of a file when deciding whether it's text,
+/// mirroring the window tools like `git`/`file` use for the same check.
+const SNIFF_LEN: usize = 8192;
+
+/// Treat a chunk containing a NUL byte as binary — the same heuristic
+/// `git`/`file` use to decide whether to diff or highlight a file.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(SNIFF_LEN).any(|&b| b == 0)
+}
+
+/// Render C0 control bytes (other than the whitespace slides rely on for
+/// layout: `\n`, `\r`, `\t`) as visible caret notation, e.g. a lone ESC
+/// becomes `^[`, so a file can't smuggle cursor moves or color changes into
+/// the presentation.
+pub fn sanitize(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        match c {
+            '\n' | '\r' | '\t' => out.push(c),
+            '\u{7f}' => out.push_str("^?"),
+            c if (c as u32) < 0x20 => {
+                out.push('^');
+                out.push((c as u8 + 0x40) as char);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_lone_esc() {
+        assert_eq!(sanitize("a\x1b[31mb"), "a^[[31mb");
+    }
+
+    #[test]
+    fn escapes_del() {
+        assert_eq!(sanitize("a\x7fb"), "a^?b");
+    }
+
+    #[test]
+    fn preserves_newline_tab_and_cr() {
+        assert_eq!(sanitize("a\nb\tc\r\n"), "a\nb\tc\r\n");
+    }
+
+    #[test]
+    fn nul_byte_is_binary() {
+        assert!(is_binary(b"abc\0def"));
+    }
+
+    #[test]
+    fn plain_text_is_not_binary() {
+        assert!(!is_binary(b"fn main() {}\n"));
+    }
+}
@@ -0,0 +1,325 @@
+//! Native inline-image rendering.
+//!
+//! Decodes images with the `image` crate and writes them straight to the
+//! terminal using whichever inline-graphics protocol the host understands,
+//! so we no longer have to shell out to `viu` (and toggle raw mode around
+//! it) just to show a picture.
+
+use crossterm::{cursor::MoveTo, terminal, QueueableCommand, Result};
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use std::{
+    env,
+    io::{self, Write},
+    path::Path,
+};
+
+/// Assumed pixel size of a single terminal cell. Crossterm has no way to
+/// query the real value, so we fall back to the width/height most terminals
+/// render a monospace cell at, matching what `viu` and friends assume.
+const CELL_PIXEL_WIDTH: u32 = 8;
+const CELL_PIXEL_HEIGHT: u32 = 16;
+
+/// Inline-graphics protocols we know how to speak, in the order we probe
+/// for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    ITerm2,
+    Kitty,
+    Sixel,
+    /// Half-block Unicode rendering; works on any terminal that supports
+    /// 24-bit color, so it's the catch-all fallback.
+    Unicode,
+}
+
+/// Guess which inline-graphics protocol the host terminal understands by
+/// inspecting `$TERM`/`$TERM_PROGRAM` (and the env vars terminals set to
+/// identify themselves), falling back to the Unicode renderer.
+pub fn detect_protocol() -> Protocol {
+    let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+
+    if term_program == "iTerm.app" {
+        return Protocol::ITerm2;
+    }
+
+    if term_program == "WezTerm" || env::var("KITTY_WINDOW_ID").is_ok() {
+        return Protocol::Kitty;
+    }
+
+    let term = env::var("TERM").unwrap_or_default();
+
+    if term.contains("kitty") {
+        return Protocol::Kitty;
+    }
+
+    // Note: we deliberately don't treat `$VTE_VERSION` as a Sixel signal.
+    // It identifies the VTE widget (GNOME Terminal, xfce4-terminal, tilix,
+    // ...), not Sixel support, and VTE doesn't support Sixel by default.
+    if term.contains("sixel") {
+        return Protocol::Sixel;
+    }
+
+    Protocol::Unicode
+}
+
+fn io_err(e: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// Decode the image at `path`, correct for its EXIF orientation (if any),
+/// scale it to fit within `cols`x`rows` terminal cells (preserving aspect
+/// ratio) and emit it to `w` using the protocol detected for the current
+/// terminal.
+pub fn render(w: &mut impl Write, path: &Path, cols: u16, rows: u16) -> Result<()> {
+    let img = image::open(path).map_err(io_err)?;
+    let img = apply_exif_orientation(img, path);
+    render_image(w, &img, cols, rows)
+}
+
+/// Read the EXIF `Orientation` tag from `path` (defaulting to `1`, i.e. no
+/// transform, when it's missing or unreadable) and apply the matching
+/// rotate/flip so photos display the way they were taken, not the way the
+/// raw pixel buffer happens to be stored.
+fn apply_exif_orientation(img: DynamicImage, path: &Path) -> DynamicImage {
+    let orientation = read_exif_orientation(path).unwrap_or(1);
+
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn read_exif_orientation(path: &Path) -> Option<u32> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut reader = std::io::BufReader::new(file);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+}
+
+/// Same as [`render`] but takes an already-decoded image, so callers that
+/// need to post-process it first (EXIF rotation, PDF rasterization, ...)
+/// don't have to round-trip through disk.
+///
+/// The fitted image is centered within the `cols`x`rows` cell grid, like
+/// every other slide type.
+pub fn render_image(w: &mut impl Write, img: &DynamicImage, cols: u16, rows: u16) -> Result<()> {
+    let (pixel_w, pixel_h) = fit_dimensions(img.width(), img.height(), cols, rows);
+    let scaled = img.resize(pixel_w, pixel_h, FilterType::Lanczos3);
+
+    let cell_cols = (pixel_w + CELL_PIXEL_WIDTH - 1) / CELL_PIXEL_WIDTH;
+    let cell_rows = (pixel_h + CELL_PIXEL_HEIGHT - 1) / CELL_PIXEL_HEIGHT;
+    let x = (cols as u32).saturating_sub(cell_cols) as u16 / 2;
+    let y = (rows as u32).saturating_sub(cell_rows) as u16 / 2;
+
+    match detect_protocol() {
+        Protocol::ITerm2 => {
+            w.queue(MoveTo(x, y))?;
+            render_iterm2(w, &scaled)
+        }
+        Protocol::Kitty => {
+            w.queue(MoveTo(x, y))?;
+            render_kitty(w, &scaled)
+        }
+        Protocol::Sixel => {
+            w.queue(MoveTo(x, y))?;
+            render_sixel(w, &scaled)
+        }
+        Protocol::Unicode => {
+            // A half-block cell holds one pixel across and two down, so
+            // shrink to that grid to stay within the same cells.
+            let half_h = CELL_PIXEL_HEIGHT / 2;
+            let blocks = img.resize_exact(cell_cols, (pixel_h + half_h - 1) / half_h, FilterType::Lanczos3);
+            render_halfblocks(w, &blocks, x, y)
+        }
+    }
+}
+
+/// Scale `(src_w, src_h)` down to fit inside a `cols`x`rows` cell grid,
+/// preserving aspect ratio, and convert the result back to pixels using the
+/// assumed cell size.
+fn fit_dimensions(src_w: u32, src_h: u32, cols: u16, rows: u16) -> (u32, u32) {
+    let max_w = cols as u32 * CELL_PIXEL_WIDTH;
+    let max_h = rows as u32 * CELL_PIXEL_HEIGHT;
+
+    let w_ratio = max_w as f64 / src_w as f64;
+    let h_ratio = max_h as f64 / src_h as f64;
+    let ratio = w_ratio.min(h_ratio).min(1.0);
+
+    (
+        ((src_w as f64 * ratio) as u32).max(1),
+        ((src_h as f64 * ratio) as u32).max(1),
+    )
+}
+
+/// iTerm2's inline-images protocol: a single OSC 1337 sequence carrying the
+/// whole PNG as base64.
+fn render_iterm2(w: &mut impl Write, img: &DynamicImage) -> Result<()> {
+    let mut png = Vec::new();
+    img.write_to(&mut png, image::ImageOutputFormat::Png)
+        .map_err(io_err)?;
+    let payload = base64::encode(&png);
+
+    write!(
+        w,
+        "\x1b]1337;File=inline=1;width={}px;height={}px;preserveAspectRatio=1:{}\x07",
+        img.width(),
+        img.height(),
+        payload
+    )?;
+    w.flush()?;
+
+    Ok(())
+}
+
+/// Kitty's graphics protocol: the base64 payload is split into ~4096-byte
+/// chunks, each its own escape sequence with `m=1` except the last (`m=0`).
+fn render_kitty(w: &mut impl Write, img: &DynamicImage) -> Result<()> {
+    const CHUNK_SIZE: usize = 4096;
+
+    let rgba = img.to_rgba8();
+    let payload = base64::encode(rgba.as_raw());
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(CHUNK_SIZE).collect();
+
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = if idx + 1 == chunks.len() { 0 } else { 1 };
+
+        if idx == 0 {
+            write!(
+                w,
+                "\x1b_Ga=T,f=32,s={},v={},m={};{}\x1b\\",
+                img.width(),
+                img.height(),
+                more,
+                std::str::from_utf8(chunk).map_err(io_err)?
+            )?;
+        } else {
+            write!(
+                w,
+                "\x1b_Gm={};{}\x1b\\",
+                more,
+                std::str::from_utf8(chunk).map_err(io_err)?
+            )?;
+        }
+    }
+    w.flush()?;
+
+    Ok(())
+}
+
+/// Sixel: quantize down to the classic 6x6x6 color cube and emit one sixel
+/// band (6 vertical pixels) at a time.
+fn render_sixel(w: &mut impl Write, img: &DynamicImage) -> Result<()> {
+    let rgb = img.to_rgb8();
+    let (width, height) = rgb.dimensions();
+
+    write!(w, "\x1bPq")?;
+
+    // Declare the 216-color cube used below.
+    for level_r in 0..6u32 {
+        for level_g in 0..6u32 {
+            for level_b in 0..6u32 {
+                let idx = level_r * 36 + level_g * 6 + level_b;
+                write!(
+                    w,
+                    "#{};2;{};{};{}",
+                    idx,
+                    level_r * 100 / 5,
+                    level_g * 100 / 5,
+                    level_b * 100 / 5
+                )?;
+            }
+        }
+    }
+
+    for band_y in (0..height).step_by(6) {
+        for color_idx in 0..216u32 {
+            let mut any = false;
+            let mut sixel_bytes = vec![0u8; width as usize];
+
+            for x in 0..width {
+                let mut mask = 0u8;
+                for bit in 0..6u32 {
+                    let y = band_y + bit;
+                    if y >= height {
+                        continue;
+                    }
+                    let px = rgb.get_pixel(x, y);
+                    if quantize(px.0) == color_idx {
+                        mask |= 1 << bit;
+                        any = true;
+                    }
+                }
+                sixel_bytes[x as usize] = mask;
+            }
+
+            if !any {
+                continue;
+            }
+
+            write!(w, "#{}", color_idx)?;
+            for b in sixel_bytes {
+                w.write_all(&[b'?' + b])?;
+            }
+            write!(w, "$")?;
+        }
+        write!(w, "-")?;
+    }
+
+    write!(w, "\x1b\\")?;
+    w.flush()?;
+
+    Ok(())
+}
+
+fn quantize(rgb: [u8; 3]) -> u32 {
+    let r = (rgb[0] as u32 * 5) / 255;
+    let g = (rgb[1] as u32 * 5) / 255;
+    let b = (rgb[2] as u32 * 5) / 255;
+
+    r * 36 + g * 6 + b
+}
+
+/// Fallback for terminals with no inline-graphics protocol: draw the image
+/// with Unicode half-block characters, using the foreground color for the
+/// top pixel of the pair and the background color for the bottom one, so a
+/// single row of cells carries two rows of pixels.
+fn render_halfblocks(w: &mut impl Write, img: &DynamicImage, origin_x: u16, origin_y: u16) -> Result<()> {
+    let rgb = img.to_rgb8();
+    let (width, height) = rgb.dimensions();
+
+    for (row, y) in (0..height).step_by(2).enumerate() {
+        w.queue(MoveTo(origin_x, origin_y + row as u16))?;
+
+        for x in 0..width {
+            let top = rgb.get_pixel(x, y);
+            let bottom = if y + 1 < height {
+                *rgb.get_pixel(x, y + 1)
+            } else {
+                *top
+            };
+
+            write!(
+                w,
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]
+            )?;
+        }
+        write!(w, "\x1b[0m")?;
+    }
+    w.flush()?;
+
+    Ok(())
+}
+
+/// Terminal cell grid, sized with [`terminal::size`], that an image should
+/// be fit into.
+pub fn terminal_cells() -> Result<(u16, u16)> {
+    terminal::size()
+}